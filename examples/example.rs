@@ -1,8 +1,9 @@
 use std::time::Duration;
 
 #[tokio::main]
+#[allow(deprecated)]
 pub async fn main(){
-    let mut battery_client = batteread::BatteryClient::new_default_name().await.unwrap();
+    let mut battery_client = batteread::BatteryClient::new().await.unwrap();
     loop {
         let battery_state = battery_client.fetch_state().await.unwrap();
         println!("{battery_state:?}");
@@ -0,0 +1,30 @@
+//! Polls the BMS as a client and simultaneously re-advertises it as a standard GATT
+//! Battery Service, so phones and Home Assistant can read the pack directly.
+
+use std::time::Duration;
+
+use batteread::{BatteryClient, BatteryGattServer};
+use tokio::sync::watch;
+
+#[tokio::main]
+#[allow(deprecated)]
+pub async fn main() {
+    let session = bluer::Session::new().await.unwrap();
+    let adapter = session.default_adapter().await.unwrap();
+    adapter.set_powered(true).await.unwrap();
+
+    let (tx, rx) = watch::channel(None);
+    let _server = BatteryGattServer::serve(&adapter, rx).await.unwrap();
+
+    let mut battery_client = BatteryClient::new().await.unwrap();
+    loop {
+        match battery_client.fetch_state().await {
+            Ok(state) => {
+                println!("{state:?}");
+                let _ = tx.send(Some(state));
+            }
+            Err(err) => println!("BATTERY: fetch_state failed: {err}"),
+        }
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
@@ -0,0 +1,14 @@
+use std::time::Duration;
+
+use futures_util::{pin_mut, StreamExt};
+
+#[tokio::main]
+#[allow(deprecated)]
+pub async fn main() {
+    let battery_client = batteread::BatteryClient::new().await.unwrap();
+    let states = battery_client.state_stream(Duration::from_secs(5));
+    pin_mut!(states);
+    while let Some(state) = states.next().await {
+        println!("{state:?}");
+    }
+}
@@ -19,34 +19,67 @@ use bluer::gatt::CharacteristicReader;
 use bluer::gatt::CharacteristicWriter;
 use bluer::Uuid;
 use bluer::{gatt::remote::Characteristic, AdapterEvent, Device};
-use crc16::{State, MODBUS};
+use futures_util::stream::{self, Stream};
 use futures_util::{pin_mut, StreamExt};
-use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWriteExt;
 use tokio::time::timeout;
 use tokio::time::{sleep, Duration};
+use tokio_util::codec::{Decoder, FramedRead};
+
+use crate::codec::{crc, FrameCodec};
+use crate::message::soc_message::{self, SocMessage};
+use crate::message::voltages_message::{self, VoltagesMessage};
 
 /// The reported state of the battery
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BatteryState {
     /// The state of charge of the battery in %
     pub state_of_charge_pct: u16,
     /// The residual capacity of the battery in Ah/100
     pub residual_capacity_cah: u16,
     pub cycles_count: u16,
-    /// The voltage of each cell in mv. The N/A value is 61001
+    /// The voltage of each cell in mv, excluding any N/A cells (the device reports unused
+    /// cell slots as 61001)
     pub cell_voltage_mv: Vec<u16>,
     /// The battery voltage in V/100
     pub battery_voltage_cv: u16,
 }
 
 
+/// Governs how a [`BatteryClient`] retries a request after a CRC failure, timeout, or a
+/// response that doesn't match what was asked for.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// How many additional attempts to make after an initial failure.
+    pub max_retries: u32,
+    /// Delay before the first retry. Each subsequent retry doubles the previous delay.
+    pub base_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_backoff: Duration::from_millis(250),
+        }
+    }
+}
+
+/// Talks to a li-gen/Modbus-like BMS directly over `bluer`.
+///
+/// Deprecated in favor of the JBD protocol stack ([`crate::protocol`], [`crate::jbd_transport`],
+/// [`crate::connection_manager`]), which has reconnect handling and a pluggable
+/// [`crate::transport::Transport`] that this predates. Kept for existing users on the
+/// li-gen pack this was originally written against.
+#[deprecated(note = "superseded by the JBD protocol stack (see `protocol`/`jbd_transport`/`connection_manager`)")]
 pub struct BatteryClient {
     device: Device,
     write: Characteristic,
     notify: Characteristic,
+    retry_policy: RetryPolicy,
 }
 
+#[allow(deprecated)]
 impl BatteryClient {
     const BLE_DEVICE_NAME: &'static str = "BT_HC6172";
     const NORDIC_UART_SERVICE_ID: &'static str = "6e400001-b5a3-f393-e0a9-e50e24dcca9e";
@@ -54,13 +87,10 @@ impl BatteryClient {
         "6e400002-b5a3-f393-e0a9-e50e24dcca9e";
     const NORDIC_UART_NOTIFY_CHARACTERISTIC_ID: &'static str =
         "6e400003-b5a3-f393-e0a9-e50e24dcca9e";
-    const MSG_HEADER: [u8; 2] = [0x01, 0x03];
-    // A verbatim message to send which requests state of voltages
-    const REQ_VOLTAGES: [u8; 8] = [0x01, 0x03, 0xd0, 0x00, 0x00, 0x26, 0xfc, 0xd0];
-    // A verbatim message to send which requests the state of change and related data
-    const REQ_SOC: [u8; 8] = [0x01, 0x03, 0xd0, 0x26, 0x00, 0x19, 0x5d, 0x0b];
-    // How long to wait without any notifications before considering the message completely received
-    const NOTIFICATION_TIMEOUT_S: i32 = 5;
+    // Outer safety net: how long to wait for a complete, validated frame before giving up
+    // entirely. The codec itself returns as soon as a frame is available, so in the common
+    // case this is never hit.
+    const NOTIFICATION_TIMEOUT_S: u64 = 5;
 
     /// Disconnect from the battery
     pub async fn stop(self) -> anyhow::Result<()> {
@@ -96,6 +126,7 @@ impl BatteryClient {
                         device,
                         write,
                         notify,
+                        retry_policy: RetryPolicy::default(),
                     });
                 }
             }
@@ -104,45 +135,153 @@ impl BatteryClient {
         Err(anyhow!("Failed to initialize bluetooth connection"))
     }
 
+    /// Use the given [`RetryPolicy`] instead of the default when a request fails.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Issue a minimal register read purely to confirm the link is alive and the framing
+    /// is in sync. Useful as a watchdog/liveness check in long-running deployments.
+    pub async fn ping(&mut self) -> anyhow::Result<()> {
+        self.read_holding_registers(soc_message::START_REGISTER, 1)
+            .await?;
+        Ok(())
+    }
+
     /// Read the current state from the battery
     pub async fn fetch_state(&mut self) -> anyhow::Result<BatteryState> {
+        let soc = SocMessage::new(
+            self.read_holding_registers(soc_message::START_REGISTER, soc_message::REGISTER_COUNT)
+                .await?,
+        );
+        let voltages = VoltagesMessage::new(
+            self.read_holding_registers(
+                voltages_message::START_REGISTER,
+                voltages_message::REGISTER_COUNT,
+            )
+            .await?,
+        );
+
+        Ok(BatteryState {
+            state_of_charge_pct: soc.state_of_charge_pct(),
+            residual_capacity_cah: soc.residual_capacity_cah(),
+            cycles_count: soc.cycles_count(),
+            cell_voltage_mv: voltages.cell_voltage_mv(),
+            battery_voltage_cv: voltages.battery_voltage_cv(),
+        })
+    }
+
+    /// Poll the battery on a fixed `interval`, yielding each [`BatteryState`] as it arrives.
+    ///
+    /// This keeps the BLE connection warm between polls rather than reconnecting every
+    /// time: `fetch_state` already reconnects via `try_connect` whenever the device has
+    /// dropped, so a transient disconnect surfaces as an `Err` item rather than ending the
+    /// stream. Compose this with `futures`/`tokio` combinators instead of hand-rolling a
+    /// `loop { fetch_state(); sleep }`.
+    pub fn state_stream(self, interval: Duration) -> impl Stream<Item = anyhow::Result<BatteryState>> {
+        stream::unfold(self, move |mut client| async move {
+            let state = client.fetch_state().await;
+            sleep(interval).await;
+            Some((state, client))
+        })
+    }
+
+    /// Read `count` 16-bit holding registers starting at `start`, so callers aren't limited
+    /// to the two register ranges [`fetch_state`](Self::fetch_state) reads. Builds a standard Modbus-RTU function-0x03
+    /// frame, sends it, and decodes the response into big-endian registers.
+    ///
+    /// The BLE link regularly delivers duplicated or stale notifications, so each attempt
+    /// opens a fresh notification subscription before writing the request -- any frame
+    /// still in flight from a previous attempt is dropped along with the old subscription
+    /// rather than risking it being misread as the response to this one. A response whose
+    /// byte count doesn't match `count` is treated the same as a CRC failure or timeout:
+    /// the request is retried, with exponential backoff, according to `retry_policy`.
+    pub async fn read_holding_registers(
+        &mut self,
+        start: u16,
+        count: u16,
+    ) -> anyhow::Result<Vec<u16>> {
         Self::try_connect(&self.device).await?;
 
+        let request = Self::build_read_request(start, count);
+        let mut attempt = 0;
+        loop {
+            match self.request_registers_once(&request, count).await {
+                Ok(registers) => {
+                    println!("BATTERY registers {start:#06x}..+{count}: {registers:?}");
+                    return Ok(registers);
+                }
+                Err(err) if attempt < self.retry_policy.max_retries => {
+                    let backoff = self.retry_policy.base_backoff * 2u32.pow(attempt);
+                    println!(
+                        "BATTERY: register read {start:#06x}..+{count} failed ({err}), retrying in {backoff:?}"
+                    );
+                    sleep(backoff).await;
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Make a single request/response round trip for [`read_holding_registers`], with no
+    /// retrying of its own.
+    async fn request_registers_once(
+        &mut self,
+        request: &[u8],
+        count: u16,
+    ) -> anyhow::Result<Vec<u16>> {
         let mut reader = self.notify.notify_io().await?;
-        self.write_msg(&Self::REQ_SOC).await?;
+        self.write_msg(request).await?;
         let rsp = Self::read_message(&mut reader).await?;
-        let nums: Vec<u16> = rsp
-            .chunks(2)
-            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
-            .collect();
-
-        println!("BATTERY SOC response: {nums:?}");
+        Self::decode_registers(count, &rsp)
+    }
 
-        let state_of_charge_pct = nums[14];
-        let residual_capacity_cah = nums[16];
-        let cycles_count = nums[19];
+    /// Build a Modbus-RTU function-0x03 "read holding registers" request frame: slave
+    /// address `0x01`, function `0x03`, start address and register count (big-endian),
+    /// followed by the MODBUS CRC16 (little-endian).
+    fn build_read_request(start: u16, count: u16) -> [u8; 8] {
+        let mut frame = [0u8; 8];
+        frame[0] = 0x01;
+        frame[1] = 0x03;
+        frame[2..4].copy_from_slice(&start.to_be_bytes());
+        frame[4..6].copy_from_slice(&count.to_be_bytes());
+        let frame_crc = crc(&frame[0..6]);
+        frame[6..8].copy_from_slice(&frame_crc);
+        frame
+    }
 
-        self.write_msg(&Self::REQ_VOLTAGES).await?;
-        let rsp = Self::read_message(&mut reader).await?;
+    /// Decode a holding-register response payload into big-endian `u16` registers,
+    /// rejecting it if its length doesn't match what was requested -- the signal that this
+    /// is a stale or mismatched frame rather than the response to our own request.
+    ///
+    /// `payload` is what [`read_message`](Self::read_message) returns: `FrameCodec` has
+    /// already stripped the Modbus address/function/byte-count header and the CRC trailer,
+    /// so this is just the `count * 2` raw register bytes, not a Modbus byte-count byte
+    /// followed by the registers.
+    ///
+    /// A Modbus-RTU function-0x03 response echoes the slave address and function code --
+    /// `FrameCodec` only accepts frames starting `0x01 0x03` -- but not the requested start
+    /// address (only a reply to a *write* does that), so there's nothing in the payload
+    /// itself to correlate against the address. The payload length is the only remaining
+    /// signal, and in practice it's enough: each attempt in
+    /// [`read_holding_registers`] opens a fresh notification subscription immediately
+    /// before writing its request, so a stale frame from a previous attempt would have to
+    /// also happen to carry a matching length to be mistaken for this one.
+    fn decode_registers(count: u16, payload: &[u8]) -> anyhow::Result<Vec<u16>> {
+        let expected_len = count as usize * 2;
+        if payload.len() != expected_len {
+            return Err(anyhow!(
+                "Register response has unexpected length: expected {expected_len} bytes, got {}",
+                payload.len()
+            ));
+        }
 
-        let nums: Vec<u16> = rsp
+        Ok(payload
             .chunks(2)
             .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
-            .collect();
-        println!("BATTERY Voltages response: {nums:?}");
-
-        let cell_voltage_mv = nums[0..32].to_vec();
-        let battery_voltage_cv = nums[37];
-
-        let state = BatteryState {
-            state_of_charge_pct,
-            residual_capacity_cah,
-            cycles_count,
-            cell_voltage_mv,
-            battery_voltage_cv,
-        };
-
-        Ok(state)
+            .collect())
     }
 
     /// Send the given bytes to the battery, via the Nordic UART write characteristic
@@ -161,110 +300,23 @@ impl BatteryClient {
     }
 
     /// Attempt to read a whole message from the device.
-    /// 
-    /// Messages are delivered over multiple notification events. Although in theory it 
-    /// is possible to know when you've received the whole message
-    /// by using the message header information, that doesn't work in practice because
-    /// you often get duplicated notifications as well as corrupted notifications.
-    /// As a result, sometimes there are more notifcations to receive after the specifed
-    /// message length has been reached and conversely, sometimes the notifcations
-    /// stop before the specified message length is reached.
-    /// 
-    /// To deal with this a timeout mechanism is used. Notifications are read
-    /// and appended to the received message until no more notifications are received 
-    /// for a short time. Then the message is considered complete. If it is corrupted then
-    /// that will be detected later during message parsing.
-    /// 
-    /// Unfortunately this introduces a minimum time to read a message of a few seconds.
-    /// However, it is the only reliable way I've found.
+    ///
+    /// Notifications are decoded through a [`FrameCodec`], which resynchronizes on the frame
+    /// header and validates the CRC as bytes arrive, so the common case returns as soon as a
+    /// single valid frame has been received instead of waiting for the notification stream to
+    /// go quiet. The fixed timeout here is only an outer safety net for the case where no
+    /// valid frame ever arrives at all.
     async fn read_message(reader: &mut CharacteristicReader) -> anyhow::Result<Vec<u8>> {
-        let mut buf = vec![0u8; reader.mtu()];
-        let mut msg = Vec::<u8>::new();
-        loop {
-            let read_result =
-                tokio::time::timeout(Duration::from_secs(NOTIFICATION_TIMEOUT_S), reader.read(&mut buf)).await;
-
-            match read_result {
-                Err(_) => {
-                    // timeout
-                    let parse_msg_result = Self::try_parse_msg(&msg[..]);
-                    match parse_msg_result {
-                        TryParseMessageResult::Ok(payload) => return Ok(payload),
-                        TryParseMessageResult::Incomplete => {
-                            let h_msg = hex::encode(&msg[..]);
-                            return Err(anyhow!("Message incomplete: {h_msg}"));
-                        }
-                        TryParseMessageResult::Invalid(e) => {
-                            let h_msg = hex::encode(&msg[..]);
-                            return Err(anyhow!("Message invalid: {e}: {h_msg}"));
-                        }
-                    }
-                }
-                Ok(Ok(0)) => {
-                    // End of stream
+        let mut framed = FramedRead::new(reader, FrameCodec);
 
-                    println!("BATTERY: End of notification stream");
-
-                    return Err(anyhow!("end of notification stream"));
-                }
-                Ok(Ok(read)) => {
-                    let h_notification = hex::encode(&buf[0..read]);
-                    println!("BATTERY: RX notification: 0x{h_notification}");
-
-                    msg.extend_from_slice(&buf[0..read]);
-                }
-                Ok(Err(err)) => {
-                    println!("BATTERY: Notification error: {err}");
-
-                    return Err(err.into());
-                }
-            }
+        match timeout(Duration::from_secs(Self::NOTIFICATION_TIMEOUT_S), framed.next()).await {
+            Ok(Some(Ok(payload))) => Ok(payload),
+            Ok(Some(Err(err))) => Err(err.into()),
+            Ok(None) => Err(anyhow!("end of notification stream")),
+            Err(_) => Err(anyhow!("Timed out waiting for a complete frame")),
         }
     }
 
-    /// Attempt to parse the given message bytes returning the payload.
-    /// 
-    /// The message format is:
-    /// 
-    /// Start Byte | End Byte     | Meaning
-    /// 0          | 1            | A constant header with value [0x01, 0x03]
-    /// 2          | 2            | The length in bytes of the rest of the message after this byte
-    /// 3          | x            | The payload
-    /// x+1        | x+2          | A MODBUS CRC over the bytes 0-x
-    fn try_parse_msg(buffer: &[u8]) -> TryParseMessageResult {
-        if buffer.len() < 3 {
-            return TryParseMessageResult::Incomplete;
-        }
-
-        let expected_header = &Self::MSG_HEADER[..];
-        if &buffer[0..2] != expected_header {
-            return TryParseMessageResult::Invalid("Unexpected header");
-        }
-
-        let expected_len = buffer[2] as usize + 5;
-        if buffer.len() < expected_len {
-            return TryParseMessageResult::Incomplete;
-        }
-
-        if buffer.len() > expected_len {
-            return TryParseMessageResult::Invalid("Too long");
-        }
-
-        let crc_actual = &buffer[buffer.len() - 2..];
-        let crc_expected = Self::crc(&buffer[0..buffer.len() - 2]);
-        if crc_actual != crc_expected {
-            return TryParseMessageResult::Invalid("CRC check failed");
-        }
-
-        let payload = buffer[3..buffer.len() - 2].to_vec();
-        TryParseMessageResult::Ok(payload)
-    }
-
-    /// Compute the CRC check value for the given bytes
-    fn crc(data: &[u8]) -> [u8; 2] {
-        State::<MODBUS>::calculate(data).to_le_bytes()
-    }
-
     fn nordic_uart_service_id() -> Uuid {
         Uuid::parse_str(Self::NORDIC_UART_SERVICE_ID).unwrap()
     }
@@ -321,49 +373,37 @@ impl BatteryClient {
 }
 
 #[test]
-fn test_try_parse_message_happy() {
-    let message =
-        hex::decode("010318240c000002a7000000000000000000000000000000000000bc90").unwrap();
-    let payload = hex::decode("240c000002a7000000000000000000000000000000000000").unwrap();
-    let result = BatteryClient::try_parse_msg(&message[..]);
-    assert_eq!(result, TryParseMessageResult::Ok(payload));
-}
-
-#[test]
-fn test_try_parse_message_no_header() {
-    let message = hex::decode("0103").unwrap();
-    let result = BatteryClient::try_parse_msg(&message[..]);
-    assert_eq!(result, TryParseMessageResult::Incomplete);
+#[allow(deprecated)]
+fn test_build_read_request() {
+    let frame = BatteryClient::build_read_request(0xd026, 2);
+    assert_eq!(frame[0..6], [0x01, 0x03, 0xd0, 0x26, 0x00, 0x02]);
+    assert_eq!(frame[6..8], crc(&frame[0..6]));
 }
 
 #[test]
-fn test_try_parse_message_incomplete() {
-    let message = hex::decode("010318240c000002a700000000000000000000000000000000bc").unwrap();
-    let result = BatteryClient::try_parse_msg(&message[..]);
-    assert_eq!(result, TryParseMessageResult::Incomplete);
+#[allow(deprecated)]
+fn test_decode_registers_round_trips_through_frame_codec() {
+    // Build a realistic 2-register response frame and run it through the actual
+    // `FrameCodec`, so this exercises what `decode_registers` is really handed by
+    // `read_message` rather than assuming its shape.
+    let data = [0x12u8, 0x34, 0x56, 0x78];
+    let mut frame = vec![0x01, 0x03, data.len() as u8];
+    frame.extend_from_slice(&data);
+    frame.extend_from_slice(&crc(&frame));
+
+    let mut buf = bytes::BytesMut::from(&frame[..]);
+    let payload = FrameCodec.decode(&mut buf).unwrap().unwrap();
+
+    assert_eq!(
+        BatteryClient::decode_registers(2, &payload).unwrap(),
+        vec![0x1234, 0x5678]
+    );
 }
 
 #[test]
-fn test_try_parse_message_bad_crc() {
-    let message =
-        hex::decode("010318240c000002a7000000000000000000000000000000000000bc91").unwrap();
-    let result = BatteryClient::try_parse_msg(&message[..]);
-    assert_eq!(result, TryParseMessageResult::Invalid("CRC check failed"));
+#[allow(deprecated)]
+fn test_decode_registers_rejects_wrong_length() {
+    let err = BatteryClient::decode_registers(2, &[0x00, 0x01]).unwrap_err();
+    assert!(err.to_string().contains("unexpected length"));
 }
 
-#[derive(PartialEq, Eq, Debug)]
-enum TryParseMessageResult {
-    Ok(Vec<u8>),
-    Incomplete,
-    Invalid(&'static str),
-}
-
-#[test]
-fn test_checksum() {
-    let payload = [
-        0x01, 0x03, 0x18, 0x24, 0x0c, 0x00, 0x00, 0x02, 0xa7, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-    ];
-    let expected = 0x90bc;
-    assert_eq!(State::<MODBUS>::calculate(&payload), expected);
-}
@@ -0,0 +1,131 @@
+//! Framing codec for the battery's notification byte stream.
+//!
+//! Frames are shaped `[0x01, 0x03, len, payload.., crc_lo, crc_hi]`. Over BLE the device
+//! regularly emits duplicated or corrupted notifications, so the decoder resynchronizes on
+//! the two-byte header and, on a CRC mismatch, drops only the leading header byte and retries
+//! rather than discarding everything that has been buffered so far.
+
+use bytes::{Buf, BytesMut};
+use crc16::{State, MODBUS};
+use std::io;
+use tokio_util::codec::{Decoder, Encoder};
+
+const MSG_HEADER: [u8; 2] = [0x01, 0x03];
+
+/// Decodes the battery's notification byte stream into complete, CRC-validated frame payloads.
+#[derive(Default)]
+pub(crate) struct FrameCodec;
+
+impl Decoder for FrameCodec {
+    type Item = Vec<u8>;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            let header_pos = src
+                .windows(MSG_HEADER.len())
+                .position(|window| window == MSG_HEADER);
+
+            let header_pos = match header_pos {
+                Some(pos) => pos,
+                None => {
+                    // Keep the final byte in case it's the first half of a split header.
+                    let keep_from = src.len().saturating_sub(1);
+                    src.advance(keep_from);
+                    return Ok(None);
+                }
+            };
+            src.advance(header_pos);
+
+            if src.len() < 3 {
+                return Ok(None);
+            }
+
+            let frame_len = src[2] as usize + 5;
+            if src.len() < frame_len {
+                return Ok(None);
+            }
+
+            let crc_expected = crc(&src[0..frame_len - 2]);
+            if src[frame_len - 2..frame_len] != crc_expected {
+                // Self-heal: this wasn't a real frame after all, resync past the header byte.
+                src.advance(1);
+                continue;
+            }
+
+            let frame = src.split_to(frame_len);
+            return Ok(Some(frame[3..frame_len - 2].to_vec()));
+        }
+    }
+}
+
+impl Encoder<&[u8]> for FrameCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: &[u8], dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.extend_from_slice(item);
+        Ok(())
+    }
+}
+
+/// Compute the MODBUS CRC16 check value for the given bytes, little-endian.
+pub(crate) fn crc(data: &[u8]) -> [u8; 2] {
+    State::<MODBUS>::calculate(data).to_le_bytes()
+}
+
+#[test]
+fn test_decode_happy() {
+    let mut buf = BytesMut::from(
+        &hex::decode("010318240c000002a7000000000000000000000000000000000000bc90").unwrap()[..],
+    );
+    let payload = hex::decode("240c000002a7000000000000000000000000000000000000").unwrap();
+    let result = FrameCodec.decode(&mut buf).unwrap();
+    assert_eq!(result, Some(payload));
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn test_decode_needs_more_data() {
+    let mut buf = BytesMut::from(&hex::decode("0103").unwrap()[..]);
+    let result = FrameCodec.decode(&mut buf).unwrap();
+    assert_eq!(result, None);
+}
+
+#[test]
+fn test_decode_incomplete_frame() {
+    let mut buf = BytesMut::from(
+        &hex::decode("010318240c000002a700000000000000000000000000000000bc").unwrap()[..],
+    );
+    let result = FrameCodec.decode(&mut buf).unwrap();
+    assert_eq!(result, None);
+}
+
+#[test]
+fn test_decode_bad_crc_resyncs_instead_of_failing() {
+    let mut buf = BytesMut::from(
+        &hex::decode("010318240c000002a7000000000000000000000000000000000000bc91").unwrap()[..],
+    );
+    let result = FrameCodec.decode(&mut buf).unwrap();
+    assert_eq!(result, None);
+}
+
+#[test]
+fn test_decode_discards_garbage_before_header() {
+    let mut buf = BytesMut::from(
+        &hex::decode("ffff010318240c000002a7000000000000000000000000000000000000bc90").unwrap()
+            [..],
+    );
+    let payload = hex::decode("240c000002a7000000000000000000000000000000000000").unwrap();
+    let result = FrameCodec.decode(&mut buf).unwrap();
+    assert_eq!(result, Some(payload));
+}
+
+#[test]
+fn test_checksum() {
+    let payload = [
+        0x01, 0x03, 0x18, 0x24, 0x0c, 0x00, 0x00, 0x02, 0xa7, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+    let expected = 0x90bc;
+    assert_eq!(State::<MODBUS>::calculate(&payload), expected);
+}
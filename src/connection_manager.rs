@@ -0,0 +1,139 @@
+//! Tracks the BLE connection explicitly and recovers automatically when the pack drops
+//! off, which cheap BLE BMS modules do often (typically when they go to sleep). `main`
+//! used to assume a single successful `connect()` and never recovered if the pack
+//! disappeared; this drives reconnection instead.
+
+use std::time::Duration;
+
+use anyhow::anyhow;
+use btleplug::api::{Central, CentralEvent, Peripheral as _};
+use btleplug::platform::PeripheralId;
+use futures_util::StreamExt;
+use uuid::Uuid;
+use tokio::time::sleep;
+
+use crate::jbd_transport::JBD_NOTIFY_CHARACTERISTIC_ID;
+use crate::scan;
+use crate::transaction::{self, DEFAULT_TRANSACTION_TIMEOUT};
+
+/// The states a managed connection moves through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Disconnected,
+    Connecting,
+    Connected,
+    Subscribed,
+}
+
+/// Connects to a peripheral and keeps it connected: on an unexpected disconnect it
+/// re-scans, re-connects, re-discovers services, and re-subscribes to the notify
+/// characteristic, backing off exponentially between attempts.
+///
+/// This stays on `btleplug`'s [`Central`] directly rather than the [`crate::transport::Transport`]
+/// abstraction used by [`crate::jbd_transport`]: reconnect here is driven by
+/// `Central::events`' disconnect notifications, which `Transport` doesn't expose.
+pub struct ConnectionManager<C: Central> {
+    central: C,
+    peripheral_id: PeripheralId,
+    service: Uuid,
+    state: ConnectionState,
+    base_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl<C: Central> ConnectionManager<C> {
+    pub fn new(central: C, peripheral_id: PeripheralId, service: Uuid) -> Self {
+        Self {
+            central,
+            peripheral_id,
+            service,
+            state: ConnectionState::Disconnected,
+            base_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    /// Connect, discover services, and subscribe to the notify characteristic, retrying
+    /// with exponential backoff on failure. Returns the ready-to-use peripheral.
+    pub async fn connect(&mut self) -> anyhow::Result<C::Peripheral> {
+        let mut backoff = self.base_backoff;
+        loop {
+            match self.try_connect().await {
+                Ok(peripheral) => return Ok(peripheral),
+                Err(err) => {
+                    println!(
+                        "BATTERY: connection attempt failed ({err}), retrying in {backoff:?}"
+                    );
+                    sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.max_backoff);
+                }
+            }
+        }
+    }
+
+    async fn try_connect(&mut self) -> anyhow::Result<C::Peripheral> {
+        self.state = ConnectionState::Connecting;
+
+        // The device may have gone to sleep and stopped advertising; a short re-scan
+        // gives it a chance to wake back up and be found again before we try to connect.
+        let _ = scan::scan(&self.central, Duration::from_secs(5), self.service).await;
+
+        let id = self.peripheral_id.clone();
+        let peripheral = transaction::retry(DEFAULT_TRANSACTION_TIMEOUT, 2, || {
+            let central = &self.central;
+            let id = id.clone();
+            async move { central.peripheral(&id).await.map_err(Into::into) }
+        })
+        .await
+        .map_err(|err| anyhow!(err.to_string()))?;
+
+        transaction::retry(DEFAULT_TRANSACTION_TIMEOUT, 2, || async {
+            peripheral.connect().await.map_err(Into::into)
+        })
+        .await
+        .map_err(|err| anyhow!(err.to_string()))?;
+
+        transaction::retry(DEFAULT_TRANSACTION_TIMEOUT, 2, || async {
+            peripheral.discover_services().await.map_err(Into::into)
+        })
+        .await
+        .map_err(|err| anyhow!(err.to_string()))?;
+        self.state = ConnectionState::Connected;
+
+        let notify_char = peripheral
+            .characteristics()
+            .into_iter()
+            .find(|c| c.uuid == JBD_NOTIFY_CHARACTERISTIC_ID)
+            .ok_or_else(|| anyhow!("Notify characteristic not found"))?;
+
+        transaction::retry(DEFAULT_TRANSACTION_TIMEOUT, 2, || async {
+            peripheral.subscribe(&notify_char).await.map_err(Into::into)
+        })
+        .await
+        .map_err(|err| anyhow!(err.to_string()))?;
+        self.state = ConnectionState::Subscribed;
+
+        Ok(peripheral)
+    }
+
+    /// Block until the managed peripheral disconnects, then mark the connection as
+    /// [`ConnectionState::Disconnected`] so the next [`Self::connect`] call re-establishes
+    /// it. Intended to run in a loop alongside whatever is using the connection.
+    pub async fn watch_for_disconnect(&mut self) -> anyhow::Result<()> {
+        let mut events = self.central.events().await?;
+        while let Some(event) = events.next().await {
+            if let CentralEvent::DeviceDisconnected(id) = event {
+                if id == self.peripheral_id {
+                    println!("BATTERY: device disconnected, will reconnect");
+                    self.state = ConnectionState::Disconnected;
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+//! Re-exposes the battery pack as a standard GATT peripheral: the Bluetooth Battery
+//! Service (`0x180F`) with a Battery Level characteristic, so phones, Home Assistant and
+//! other off-the-shelf BLE battery consumers can read the pack without speaking the
+//! proprietary Modbus-like protocol used to talk to the BMS itself.
+//!
+//! Alongside the standard service this also advertises a custom service reporting the
+//! per-cell and pack voltages, which the Battery Service profile has no room for.
+
+use bluer::gatt::local::{
+    Application, ApplicationHandle, Characteristic, CharacteristicNotify,
+    CharacteristicNotifyMethod, CharacteristicRead, Service,
+};
+use bluer::{Adapter, Uuid};
+use futures_util::FutureExt;
+use tokio::sync::watch;
+
+use crate::BatteryState;
+
+const BATTERY_SERVICE_ID: &str = "0000180f-0000-1000-8000-00805f9b34fb";
+const BATTERY_LEVEL_CHARACTERISTIC_ID: &str = "00002a19-0000-1000-8000-00805f9b34fb";
+
+// A custom service reporting the per-cell and pack voltages the standard Battery Service
+// has no characteristic for.
+const VOLTAGES_SERVICE_ID: &str = "a0f8c1f0-8d35-4d9e-9f1a-2f6a9c3b0001";
+const CELL_VOLTAGES_CHARACTERISTIC_ID: &str = "a0f8c1f0-8d35-4d9e-9f1a-2f6a9c3b0002";
+const PACK_VOLTAGE_CHARACTERISTIC_ID: &str = "a0f8c1f0-8d35-4d9e-9f1a-2f6a9c3b0003";
+
+/// A GATT server that re-publishes a shared, continually-refreshed [`BatteryState`] over
+/// the standard Battery Service plus a custom per-cell-voltages service.
+pub struct BatteryGattServer {
+    _app_handle: ApplicationHandle,
+}
+
+impl BatteryGattServer {
+    /// Register the GATT application on `adapter`. `state` should be updated (e.g. via a
+    /// [`watch::Sender`](tokio::sync::watch::Sender) fed by `BatteryClient::fetch_state`)
+    /// each time a fresh reading arrives; subscribers are notified automatically.
+    pub async fn serve(
+        adapter: &Adapter,
+        state: watch::Receiver<Option<BatteryState>>,
+    ) -> anyhow::Result<Self> {
+        let app = Application {
+            services: vec![
+                Self::battery_service(state.clone()),
+                Self::voltages_service(state),
+            ],
+            ..Default::default()
+        };
+
+        let app_handle = adapter.serve_gatt_application(app).await?;
+        Ok(Self {
+            _app_handle: app_handle,
+        })
+    }
+
+    fn battery_service(state: watch::Receiver<Option<BatteryState>>) -> Service {
+        let read_state = state.clone();
+
+        Service {
+            uuid: Uuid::parse_str(BATTERY_SERVICE_ID).unwrap(),
+            primary: true,
+            characteristics: vec![Characteristic {
+                uuid: Uuid::parse_str(BATTERY_LEVEL_CHARACTERISTIC_ID).unwrap(),
+                read: Some(CharacteristicRead {
+                    read: true,
+                    fun: Box::new(move |_req| {
+                        let state = read_state.clone();
+                        async move { Ok(vec![state_of_charge_byte(&state)]) }.boxed()
+                    }),
+                    ..Default::default()
+                }),
+                notify: Some(CharacteristicNotify {
+                    notify: true,
+                    method: CharacteristicNotifyMethod::Fun(Box::new(move |mut notifier| {
+                        let mut state = state.clone();
+                        async move {
+                            while state.changed().await.is_ok() {
+                                if notifier
+                                    .notify(vec![state_of_charge_byte(&state)])
+                                    .await
+                                    .is_err()
+                                {
+                                    break;
+                                }
+                            }
+                        }
+                        .boxed()
+                    })),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn voltages_service(state: watch::Receiver<Option<BatteryState>>) -> Service {
+        let cell_state = state.clone();
+        let pack_state = state;
+
+        Service {
+            uuid: Uuid::parse_str(VOLTAGES_SERVICE_ID).unwrap(),
+            primary: true,
+            characteristics: vec![
+                Characteristic {
+                    uuid: Uuid::parse_str(CELL_VOLTAGES_CHARACTERISTIC_ID).unwrap(),
+                    read: Some(CharacteristicRead {
+                        read: true,
+                        fun: Box::new(move |_req| {
+                            let state = cell_state.clone();
+                            async move { Ok(cell_voltage_bytes(&state)) }.boxed()
+                        }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                Characteristic {
+                    uuid: Uuid::parse_str(PACK_VOLTAGE_CHARACTERISTIC_ID).unwrap(),
+                    read: Some(CharacteristicRead {
+                        read: true,
+                        fun: Box::new(move |_req| {
+                            let state = pack_state.clone();
+                            async move { Ok(pack_voltage_bytes(&state)) }.boxed()
+                        }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }
+    }
+}
+
+fn state_of_charge_byte(state: &watch::Receiver<Option<BatteryState>>) -> u8 {
+    state
+        .borrow()
+        .as_ref()
+        .map(|s| s.state_of_charge_pct.min(100) as u8)
+        .unwrap_or(0)
+}
+
+fn cell_voltage_bytes(state: &watch::Receiver<Option<BatteryState>>) -> Vec<u8> {
+    state
+        .borrow()
+        .as_ref()
+        .map(|s| s.cell_voltage_mv.iter().flat_map(|v| v.to_le_bytes()).collect())
+        .unwrap_or_default()
+}
+
+fn pack_voltage_bytes(state: &watch::Receiver<Option<BatteryState>>) -> Vec<u8> {
+    state
+        .borrow()
+        .as_ref()
+        .map(|s| s.battery_voltage_cv.to_le_bytes().to_vec())
+        .unwrap_or_default()
+}
@@ -0,0 +1,82 @@
+//! GATT write/notify transport for the JBD BMS, built on the [`Transport`] abstraction so
+//! this doesn't care whether GATT operations are driven by `btleplug` or the `bluer`/D-Bus
+//! backend underneath.
+//!
+//! JBD packs expose a proprietary service (`0xFF00`) with a write characteristic
+//! (`0xFF02`) and a notify characteristic (`0xFF01`), following the same
+//! write-command/notify-response shape as the Nordic UART service used elsewhere in this
+//! crate.
+
+use anyhow::{anyhow, bail};
+use futures_util::StreamExt;
+use uuid::Uuid;
+
+use crate::transaction::{self, DEFAULT_TRANSACTION_TIMEOUT};
+use crate::transport::Transport;
+
+pub const JBD_SERVICE_ID: Uuid = Uuid::from_u128(0x0000ff00_0000_1000_8000_00805f9b34fb);
+pub const JBD_WRITE_CHARACTERISTIC_ID: Uuid = Uuid::from_u128(0x0000ff02_0000_1000_8000_00805f9b34fb);
+pub const JBD_NOTIFY_CHARACTERISTIC_ID: Uuid = Uuid::from_u128(0x0000ff01_0000_1000_8000_00805f9b34fb);
+
+/// Request `register` from the BMS and return the complete response frame.
+///
+/// Subscribes to the notify characteristic, writes the request frame to the write
+/// characteristic, then accumulates notification fragments until a complete frame has been
+/// assembled (starts with `0xDD`, ends with `0x77`, and its declared length is satisfied).
+/// Every GATT operation -- the subscribe, opening the notification stream, the write, and
+/// each await on the next notification fragment -- is bounded by the default transaction
+/// timeout, so a single hung transport call can't block the caller forever. The subscribe
+/// and the write are also retried a couple of times; a notification that doesn't arrive in
+/// time fails the request without a retry.
+pub async fn request<T: Transport>(
+    transport: &T,
+    peripheral: &T::Peripheral,
+    register: u8,
+) -> anyhow::Result<Vec<u8>> {
+    transaction::retry(DEFAULT_TRANSACTION_TIMEOUT, 2, || async {
+        transport
+            .subscribe(peripheral, JBD_NOTIFY_CHARACTERISTIC_ID)
+            .await
+    })
+    .await
+    .map_err(|err| anyhow!(err.to_string()))?;
+
+    let mut notifications = transaction::with_timeout(DEFAULT_TRANSACTION_TIMEOUT, async {
+        transport
+            .notifications(peripheral, JBD_NOTIFY_CHARACTERISTIC_ID)
+            .await
+    })
+    .await
+    .map_err(|err| anyhow!(err.to_string()))?;
+
+    let frame = crate::protocol::build_read_request(register);
+    transaction::retry(DEFAULT_TRANSACTION_TIMEOUT, 2, || async {
+        transport
+            .write(peripheral, JBD_WRITE_CHARACTERISTIC_ID, &frame)
+            .await
+    })
+    .await
+    .map_err(|err| anyhow!(err.to_string()))?;
+
+    let mut response = Vec::new();
+    loop {
+        let notification = tokio::time::timeout(DEFAULT_TRANSACTION_TIMEOUT, notifications.next())
+            .await
+            .map_err(|_| anyhow!("Timed out waiting for a notification"))?;
+        let Some(notification) = notification else {
+            bail!("Notification stream ended before a complete response frame was received");
+        };
+
+        response.extend_from_slice(&notification);
+        if is_complete_frame(&response) {
+            return Ok(response);
+        }
+    }
+}
+
+fn is_complete_frame(buf: &[u8]) -> bool {
+    buf.len() >= 7
+        && buf.first() == Some(&0xdd)
+        && buf.last() == Some(&0x77)
+        && buf.len() == buf[3] as usize + 7
+}
@@ -1,27 +1,40 @@
 //! Read status data from certain models of LiFePO4 Battery Management Systems over Bluetooth Low Energy
-//! 
+//!
+//! This crate has grown two BMS protocol stacks that don't share a transport, because they
+//! were reverse-engineered from two different packs:
+//!
+//! - [`BatteryClient`] talks the li-gen/Modbus-like protocol over `bluer` directly. This is
+//!   the original stack and is **deprecated**: it predates [`connection_manager`] and
+//!   [`transport`], so it only reconnects on demand at the start of each read, with no
+//!   connection-state tracking, backoff or pluggable transport of its own. It's
+//!   kept only for existing users on that pack; new code should prefer the JBD stack below.
+//! - [`protocol`] (JBD/Overkill-Solar), [`jbd_transport`], [`connection_manager`] and
+//!   [`transport`] together are the current, actively-developed stack, driven by the
+//!   `main` binary. It's what new packs and new features should build on.
+//!
 //! Tested with a 400ah 24v battery manufactured by <https://www.li-gen.net/> and sold around the year 2022.
-//! 
+//!
 //! The BMS has a BLE interface. On top of that the NordicUART protocol is used for serial communication.
 //! On top of that there seems to be a proprietary request-response protocol which I have attempted to partially
 //! reverse engineer.
-//! 
+//!
 //! Currently the following data can be accessed:
-//! 
+//!
 //! - State of charge (%)
 //! - Residual capacity (Ah)
 //! - Cycles (count)
 //! - Cell voltages (v)
 //! - Battery voltage (v)
-//! 
+//!
 //! # Example
-//! 
+//!
 //! ```rust
 //! # use std::time::Duration;
 //! #
 //! # #[tokio::main]
 //! # pub async fn main(){
-//!     let mut battery_client = batteread::BatteryClient::new_default_name().await.unwrap();
+//!     #[allow(deprecated)]
+//!     let mut battery_client = batteread::BatteryClient::new().await.unwrap();
 //!     loop {
 //!         let battery_state = battery_client.fetch_state().await.unwrap();
 //!         println!("{battery_state:?}");
@@ -31,8 +44,15 @@
 //! ```
 
 mod battery_client;
-mod battery_state;
+mod codec;
+pub mod connection_manager;
+pub mod gatt_server;
+pub mod jbd_transport;
 mod message;
+pub mod protocol;
+pub mod scan;
+pub mod transaction;
+pub mod transport;
 
-pub use battery_client::BatteryClient;
-pub use battery_state::BatteryState;
\ No newline at end of file
+pub use battery_client::{BatteryClient, BatteryState, RetryPolicy};
+pub use gatt_server::BatteryGattServer;
\ No newline at end of file
@@ -0,0 +1,2 @@
+pub(crate) mod soc_message;
+pub(crate) mod voltages_message;
@@ -1,27 +1,26 @@
-/// A verbatim message to send which requests state of voltages
-pub (crate) const REQUEST: [u8; 8] = [0x01, 0x03, 0xd0, 0x00, 0x00, 0x26, 0xfc, 0xd0];
+/// The holding register range which reports cell and pack voltages
+pub(crate) const START_REGISTER: u16 = 0xd000;
+pub(crate) const REGISTER_COUNT: u16 = 0x26;
 
 const CELL_VOLTAGE_NA_VALUE: u16 = 61001;
 
 /// A message type which contains data about battery and cell voltages.
-pub (crate) struct VoltagesMessage(Vec<u16>);
+pub(crate) struct VoltagesMessage(Vec<u16>);
 
-impl VoltagesMessage{
-    pub fn new(data: Vec<u8>) -> Self{
-        let nums: Vec<u16> = data
-            .chunks(2)
-            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
-            .collect();
-        println!("BATTERY Voltages response: {nums:?}");
-        Self(nums)
+impl VoltagesMessage {
+    pub fn new(registers: Vec<u16>) -> Self {
+        Self(registers)
     }
 
     pub fn cell_voltage_mv(&self) -> Vec<u16> {
-        self.0[0..32].iter().cloned().filter(|&v| v != CELL_VOLTAGE_NA_VALUE).collect()
+        self.0[0..32]
+            .iter()
+            .cloned()
+            .filter(|&v| v != CELL_VOLTAGE_NA_VALUE)
+            .collect()
     }
 
     pub fn battery_voltage_cv(&self) -> u16 {
         self.0[37]
     }
 }
-
@@ -0,0 +1,265 @@
+//! Parses the JBD / Overkill-Solar BMS register protocol.
+//!
+//! See <https://github.com/FurTrader/OverkillSolarBMS/blob/master/Comm_Protocol_Documentation/JBD_REGISTER_MAP.md>
+//! for the (unofficial) register map this is reverse engineered from.
+//!
+//! A read request is `0xDD 0xA5 <reg> 0x00 <chk_hi> <chk_lo> 0x77`. A response is
+//! `0xDD <reg> <status> <len> <data[len]> <chk_hi> <chk_lo> 0x77`, where `status` is `0x00`
+//! on success and the checksum is `0x10000 - (length + sum_of_data_bytes)`, computed over
+//! the length byte and whatever data follows it (for a request there is no data, so it's
+//! just the length byte plus the register byte preceding it).
+
+use anyhow::bail;
+
+pub const REG_BASIC_INFO: u8 = 0x03;
+pub const REG_CELL_VOLTAGES: u8 = 0x04;
+pub const REG_DEVICE_NAME: u8 = 0x05;
+
+const START_BYTE: u8 = 0xdd;
+const END_BYTE: u8 = 0x77;
+const READ_OK_STATUS: u8 = 0x00;
+
+/// Build a read-request frame for the given register.
+pub fn build_read_request(register: u8) -> [u8; 7] {
+    let len = 0u8;
+    let checksum = checksum(&[register, len]);
+    [
+        START_BYTE,
+        0xa5,
+        register,
+        len,
+        (checksum >> 8) as u8,
+        checksum as u8,
+        END_BYTE,
+    ]
+}
+
+/// Validate and strip the envelope off a response frame, returning the register and the
+/// data payload.
+fn parse_response(frame: &[u8]) -> anyhow::Result<(u8, Vec<u8>)> {
+    if frame.len() < 7 {
+        bail!("Response frame too short: {} bytes", frame.len());
+    }
+    if frame[0] != START_BYTE {
+        bail!("Response frame has the wrong start byte: {:#04x}", frame[0]);
+    }
+    if frame[frame.len() - 1] != END_BYTE {
+        bail!(
+            "Response frame has the wrong end byte: {:#04x}",
+            frame[frame.len() - 1]
+        );
+    }
+
+    let register = frame[1];
+    let status = frame[2];
+    if status != READ_OK_STATUS {
+        bail!("Device reported error status {status:#04x} for register {register:#04x}");
+    }
+
+    let len = frame[3] as usize;
+    if frame.len() != len + 7 {
+        bail!(
+            "Response length field ({len}) doesn't match frame length ({})",
+            frame.len()
+        );
+    }
+
+    let data = frame[4..4 + len].to_vec();
+    let checksum_expected = checksum(&frame[3..4 + len]);
+    let checksum_actual = u16::from_be_bytes([frame[4 + len], frame[5 + len]]);
+    if checksum_actual != checksum_expected {
+        bail!(
+            "Response checksum mismatch for register {register:#04x}: expected {checksum_expected:#06x}, got {checksum_actual:#06x}"
+        );
+    }
+
+    Ok((register, data))
+}
+
+fn checksum(data: &[u8]) -> u16 {
+    let sum: u32 = data.iter().map(|&b| b as u32).sum();
+    0x10000u32.wrapping_sub(sum) as u16
+}
+
+/// Basic pack status: voltage, current, capacity, cycles, balance/protection flags, state
+/// of charge, FET status and NTC temperatures. Decoded from register [`REG_BASIC_INFO`].
+#[derive(Debug, Clone)]
+pub struct BasicInfo {
+    pub total_voltage_cv: u16,
+    pub current_ca: i16,
+    pub residual_capacity_cah: u16,
+    pub nominal_capacity_cah: u16,
+    pub cycle_count: u16,
+    pub balance_status: u16,
+    pub protection_status: u16,
+    pub state_of_charge_pct: u8,
+    pub charging_fet_on: bool,
+    pub discharging_fet_on: bool,
+    pub cell_count: u8,
+    pub temperatures_c: Vec<f32>,
+}
+
+impl BasicInfo {
+    /// Parse a response frame for register [`REG_BASIC_INFO`].
+    pub fn parse(frame: &[u8]) -> anyhow::Result<Self> {
+        let (register, data) = parse_response(frame)?;
+        if register != REG_BASIC_INFO {
+            bail!("Expected basic info register {REG_BASIC_INFO:#04x}, got {register:#04x}");
+        }
+        if data.len() < 23 {
+            bail!("Basic info payload too short: {} bytes", data.len());
+        }
+
+        let ntc_count = data[22] as usize;
+        if data.len() < 23 + ntc_count * 2 {
+            bail!("Basic info payload too short for {ntc_count} NTC readings");
+        }
+
+        let fet_status = data[20];
+        let temperatures_c = data[23..23 + ntc_count * 2]
+            .chunks(2)
+            .map(|bytes| {
+                let raw_tenths_kelvin = u16::from_be_bytes([bytes[0], bytes[1]]) as f32;
+                (raw_tenths_kelvin - 2731.0) / 10.0
+            })
+            .collect();
+
+        Ok(Self {
+            total_voltage_cv: u16::from_be_bytes([data[0], data[1]]),
+            current_ca: i16::from_be_bytes([data[2], data[3]]),
+            residual_capacity_cah: u16::from_be_bytes([data[4], data[5]]),
+            nominal_capacity_cah: u16::from_be_bytes([data[6], data[7]]),
+            cycle_count: u16::from_be_bytes([data[8], data[9]]),
+            balance_status: u16::from_be_bytes([data[12], data[13]]),
+            protection_status: u16::from_be_bytes([data[16], data[17]]),
+            state_of_charge_pct: data[19],
+            charging_fet_on: fet_status & 0b01 != 0,
+            discharging_fet_on: fet_status & 0b10 != 0,
+            cell_count: data[21],
+            temperatures_c,
+        })
+    }
+}
+
+/// Per-cell voltages in mV, decoded from register [`REG_CELL_VOLTAGES`].
+#[derive(Debug, Clone)]
+pub struct CellVoltages(pub Vec<u16>);
+
+impl CellVoltages {
+    /// Parse a response frame for register [`REG_CELL_VOLTAGES`].
+    pub fn parse(frame: &[u8]) -> anyhow::Result<Self> {
+        let (register, data) = parse_response(frame)?;
+        if register != REG_CELL_VOLTAGES {
+            bail!("Expected cell voltages register {REG_CELL_VOLTAGES:#04x}, got {register:#04x}");
+        }
+        Ok(Self(
+            data.chunks(2)
+                .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+                .collect(),
+        ))
+    }
+}
+
+/// The device's self-reported name, decoded from register [`REG_DEVICE_NAME`].
+#[derive(Debug, Clone)]
+pub struct DeviceName(pub String);
+
+impl DeviceName {
+    /// Parse a response frame for register [`REG_DEVICE_NAME`].
+    pub fn parse(frame: &[u8]) -> anyhow::Result<Self> {
+        let (register, data) = parse_response(frame)?;
+        if register != REG_DEVICE_NAME {
+            bail!("Expected device name register {REG_DEVICE_NAME:#04x}, got {register:#04x}");
+        }
+        Ok(Self(String::from_utf8_lossy(&data).into_owned()))
+    }
+}
+
+#[test]
+fn test_checksum() {
+    assert_eq!(checksum(&[REG_BASIC_INFO, 0]), 0xfffd);
+}
+
+#[test]
+fn test_build_read_request() {
+    assert_eq!(
+        build_read_request(REG_BASIC_INFO),
+        [0xdd, 0xa5, REG_BASIC_INFO, 0x00, 0xff, 0xfd, 0x77]
+    );
+}
+
+#[test]
+fn test_parse_response_rejects_wrong_start_byte() {
+    let mut frame = basic_info_frame();
+    frame[0] = 0x00;
+    assert!(parse_response(&frame).is_err());
+}
+
+#[test]
+fn test_parse_response_rejects_wrong_end_byte() {
+    let mut frame = basic_info_frame();
+    let last = frame.len() - 1;
+    frame[last] = 0x00;
+    assert!(parse_response(&frame).is_err());
+}
+
+#[test]
+fn test_parse_response_rejects_bad_checksum() {
+    let mut frame = basic_info_frame();
+    let last = frame.len() - 1;
+    frame[last - 1] ^= 0xff;
+    assert!(parse_response(&frame).is_err());
+}
+
+#[test]
+fn test_parse_response_rejects_truncated_frame() {
+    let frame = basic_info_frame();
+    assert!(parse_response(&frame[..frame.len() - 3]).is_err());
+}
+
+#[test]
+fn test_basic_info_parse_happy_path() {
+    let info = BasicInfo::parse(&basic_info_frame()).unwrap();
+    assert_eq!(info.total_voltage_cv, 0x0ce4);
+    assert_eq!(info.current_ca, -500);
+    assert_eq!(info.residual_capacity_cah, 0x1f40);
+    assert_eq!(info.nominal_capacity_cah, 0x2710);
+    assert_eq!(info.cycle_count, 42);
+    assert_eq!(info.balance_status, 3);
+    assert_eq!(info.protection_status, 0);
+    assert_eq!(info.state_of_charge_pct, 80);
+    assert!(info.charging_fet_on);
+    assert!(info.discharging_fet_on);
+    assert_eq!(info.cell_count, 4);
+    assert!(info.temperatures_c.is_empty());
+}
+
+#[test]
+fn test_basic_info_parse_rejects_wrong_register() {
+    assert!(BasicInfo::parse(&cell_voltages_frame()).is_err());
+}
+
+#[test]
+fn test_cell_voltages_parse() {
+    let voltages = CellVoltages::parse(&cell_voltages_frame()).unwrap();
+    assert_eq!(voltages.0, vec![3500, 3502]);
+}
+
+/// A `REG_BASIC_INFO` response for a pack at 33.00V, drawing 5.00A, 80% charged, with 4
+/// cells, both FETs on, and no NTC sensors.
+#[cfg(test)]
+fn basic_info_frame() -> Vec<u8> {
+    vec![
+        0xdd, REG_BASIC_INFO, READ_OK_STATUS, 0x17, 0x0c, 0xe4, 0xfe, 0x0c, 0x1f, 0x40, 0x27,
+        0x10, 0x00, 0x2a, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x50, 0x03, 0x04,
+        0x00, 0xfc, 0xd5, 0x77,
+    ]
+}
+
+/// A `REG_CELL_VOLTAGES` response for two cells at 3500mV and 3502mV.
+#[cfg(test)]
+fn cell_voltages_frame() -> Vec<u8> {
+    vec![
+        0xdd, REG_CELL_VOLTAGES, READ_OK_STATUS, 0x04, 0x0d, 0xac, 0x0d, 0xae, 0xfe, 0x88, 0x77,
+    ]
+}
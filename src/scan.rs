@@ -0,0 +1,68 @@
+//! Event-driven BLE scanning, in place of a fixed sleep followed by a single
+//! `peripherals()` snapshot: advertisements are collected as they arrive from
+//! [`Central::events`], so a result's RSSI reflects what was actually seen rather than
+//! whatever happened to be cached at the end of a blind wait.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use btleplug::api::{BDAddr, Central, CentralEvent, Peripheral as _, ScanFilter};
+use btleplug::platform::PeripheralId;
+use futures_util::StreamExt;
+use tokio::time::timeout;
+use uuid::Uuid;
+
+/// A single scan result: the discovered device's address, advertised name (if any), and
+/// most recently seen RSSI.
+#[derive(Debug, Clone)]
+pub struct ScanResult {
+    pub address: BDAddr,
+    pub local_name: Option<String>,
+    pub rssi: Option<i16>,
+}
+
+/// Scan for `duration`, filtering advertisements to the given `service`, and return the
+/// discovered devices sorted by signal strength (strongest first) so a user with several
+/// packs nearby can pick the closest one.
+pub async fn scan<C: Central>(
+    adapter: &C,
+    duration: Duration,
+    service: Uuid,
+) -> anyhow::Result<Vec<ScanResult>> {
+    let filter = ScanFilter {
+        services: vec![service],
+    };
+    adapter.start_scan(filter).await?;
+
+    let mut events = adapter.events().await?;
+    let mut results: HashMap<PeripheralId, ScanResult> = HashMap::new();
+
+    let _ = timeout(duration, async {
+        while let Some(event) = events.next().await {
+            let id = match event {
+                CentralEvent::DeviceDiscovered(id) | CentralEvent::DeviceUpdated(id) => id,
+                _ => continue,
+            };
+
+            if let Ok(peripheral) = adapter.peripheral(&id).await {
+                if let Ok(Some(props)) = peripheral.properties().await {
+                    results.insert(
+                        id,
+                        ScanResult {
+                            address: props.address,
+                            local_name: props.local_name,
+                            rssi: props.rssi,
+                        },
+                    );
+                }
+            }
+        }
+    })
+    .await;
+
+    adapter.stop_scan().await?;
+
+    let mut results: Vec<ScanResult> = results.into_values().collect();
+    results.sort_by_key(|r| std::cmp::Reverse(r.rssi.unwrap_or(i16::MIN)));
+    Ok(results)
+}
@@ -0,0 +1,78 @@
+//! Bounds how long a single GATT operation (connect, discover, write, or an await on a
+//! notification) is allowed to run for, and retries it a fixed number of times.
+//!
+//! Connecting, discovering services, writing, and awaiting notifications can all hang
+//! indefinitely with `btleplug`. The Bluetooth spec's own transaction-timeout rule is 30
+//! seconds, so that's the default deadline here; past it an operation is treated as a
+//! failed transaction ([`Error::Timeout`]) rather than left to hang forever.
+
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+/// The default GATT transaction deadline, per the Bluetooth spec's 30-second
+/// transaction-timeout rule.
+pub const DEFAULT_TRANSACTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A failed GATT transaction.
+#[derive(Debug)]
+pub enum Error {
+    /// The operation didn't complete within the configured deadline.
+    Timeout,
+    /// Any other failure, with its original context preserved.
+    Other(anyhow::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Timeout => write!(f, "GATT transaction timed out"),
+            Error::Other(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<anyhow::Error> for Error {
+    fn from(err: anyhow::Error) -> Self {
+        Error::Other(err)
+    }
+}
+
+/// Run `fut`, turning a deadline overrun into [`Error::Timeout`] instead of hanging.
+pub async fn with_timeout<T, Fut>(deadline: Duration, fut: Fut) -> Result<T, Error>
+where
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    match tokio::time::timeout(deadline, fut).await {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(err)) => Err(Error::Other(err)),
+        Err(_) => Err(Error::Timeout),
+    }
+}
+
+/// Run `make_future` under [`with_timeout`], retrying up to `max_retries` additional
+/// times if it times out or fails, so a single hung or dropped transaction doesn't abort
+/// the whole run.
+pub async fn retry<T, F, Fut>(
+    deadline: Duration,
+    max_retries: u32,
+    mut make_future: F,
+) -> Result<T, Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match with_timeout(deadline, make_future()).await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < max_retries => {
+                println!("BATTERY: GATT transaction failed ({err}), retrying");
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! A [`Transport`] driven by the system Bluetooth daemon over D-Bus, via `bluer`, for when
+//! the `btleplug` path proves fragile -- it ultimately talks to the same BlueZ daemon, but
+//! through a layer of its own. Enabled by the `bluez-backend` Cargo feature.
+
+use std::time::Duration;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use bluer::gatt::remote::Characteristic;
+use bluer::{AdapterEvent, Device};
+use futures_util::stream::BoxStream;
+use futures_util::{pin_mut, StreamExt};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use uuid::Uuid;
+
+use crate::scan::ScanResult;
+use crate::transport::Transport;
+
+pub struct BluezTransport {
+    adapter: bluer::Adapter,
+}
+
+impl BluezTransport {
+    pub async fn new() -> anyhow::Result<Self> {
+        let session = bluer::Session::new().await?;
+        let adapter = session.default_adapter().await?;
+        adapter.set_powered(true).await?;
+        Ok(Self { adapter })
+    }
+}
+
+#[async_trait]
+impl Transport for BluezTransport {
+    type Peripheral = Device;
+
+    async fn scan(&self, service: Uuid, duration: Duration) -> anyhow::Result<Vec<ScanResult>> {
+        let discover = self.adapter.discover_devices().await?;
+        pin_mut!(discover);
+        let service = to_bluer_uuid(service);
+
+        let mut results = Vec::new();
+        let _ = tokio::time::timeout(duration, async {
+            while let Some(event) = discover.next().await {
+                let AdapterEvent::DeviceAdded(addr) = event else {
+                    continue;
+                };
+                let Ok(device) = self.adapter.device(addr) else {
+                    continue;
+                };
+                let uuids = device.uuids().await.ok().flatten().unwrap_or_default();
+                if !uuids.contains(&service) {
+                    continue;
+                }
+                results.push(ScanResult {
+                    address: btleplug::api::BDAddr::from(addr.0),
+                    local_name: device.name().await.ok().flatten(),
+                    rssi: device.rssi().await.ok().flatten(),
+                });
+            }
+        })
+        .await;
+
+        Ok(results)
+    }
+
+    async fn connect(&self, result: &ScanResult) -> anyhow::Result<Self::Peripheral> {
+        let device = self
+            .adapter
+            .device(bluer::Address(result.address.into_inner()))?;
+        device.connect().await?;
+        Ok(device)
+    }
+
+    async fn subscribe(
+        &self,
+        peripheral: &Self::Peripheral,
+        notify_characteristic: Uuid,
+    ) -> anyhow::Result<()> {
+        // `bluer` has no separate subscribe step: opening the notify I/O stream (done
+        // lazily in `notifications` below) is what starts the subscription.
+        find_characteristic(peripheral, notify_characteristic).await?;
+        Ok(())
+    }
+
+    async fn write(
+        &self,
+        peripheral: &Self::Peripheral,
+        write_characteristic: Uuid,
+        data: &[u8],
+    ) -> anyhow::Result<()> {
+        let characteristic = find_characteristic(peripheral, write_characteristic).await?;
+        let mut writer = characteristic.write_io().await?;
+        writer.write_all(data).await?;
+        Ok(())
+    }
+
+    async fn notifications(
+        &self,
+        peripheral: &Self::Peripheral,
+        notify_characteristic: Uuid,
+    ) -> anyhow::Result<BoxStream<'static, Vec<u8>>> {
+        let characteristic = find_characteristic(peripheral, notify_characteristic).await?;
+        let reader = characteristic.notify_io().await?;
+
+        Ok(futures_util::stream::unfold(reader, |mut reader| async move {
+            let mut buf = vec![0u8; reader.mtu()];
+            match reader.read(&mut buf).await {
+                Ok(0) | Err(_) => None,
+                Ok(n) => Some((buf[..n].to_vec(), reader)),
+            }
+        })
+        .boxed())
+    }
+}
+
+async fn find_characteristic(device: &Device, id: Uuid) -> anyhow::Result<Characteristic> {
+    let id = to_bluer_uuid(id);
+    for service in device.services().await? {
+        for characteristic in service.characteristics().await? {
+            if characteristic.uuid().await? == id {
+                return Ok(characteristic);
+            }
+        }
+    }
+    Err(anyhow!("Characteristic {id} not found"))
+}
+
+fn to_bluer_uuid(id: Uuid) -> bluer::Uuid {
+    bluer::Uuid::from_bytes(*id.as_bytes())
+}
@@ -0,0 +1,89 @@
+//! The default [`Transport`], backed by `btleplug`.
+
+use std::time::Duration;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use btleplug::api::{Central, Characteristic, Peripheral as _, WriteType};
+use btleplug::platform::{Adapter, Peripheral};
+use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
+use uuid::Uuid;
+
+use crate::scan::{self, ScanResult};
+use crate::transport::Transport;
+
+pub struct BtleplugTransport {
+    adapter: Adapter,
+}
+
+impl BtleplugTransport {
+    pub fn new(adapter: Adapter) -> Self {
+        Self { adapter }
+    }
+}
+
+#[async_trait]
+impl Transport for BtleplugTransport {
+    type Peripheral = Peripheral;
+
+    async fn scan(&self, service: Uuid, duration: Duration) -> anyhow::Result<Vec<ScanResult>> {
+        scan::scan(&self.adapter, duration, service).await
+    }
+
+    async fn connect(&self, result: &ScanResult) -> anyhow::Result<Self::Peripheral> {
+        let peripheral = self
+            .adapter
+            .peripherals()
+            .await?
+            .into_iter()
+            .find(|p| p.address() == result.address)
+            .ok_or_else(|| anyhow!("{} is no longer visible", result.address))?;
+        peripheral.connect().await?;
+        peripheral.discover_services().await?;
+        Ok(peripheral)
+    }
+
+    async fn subscribe(
+        &self,
+        peripheral: &Self::Peripheral,
+        notify_characteristic: Uuid,
+    ) -> anyhow::Result<()> {
+        let characteristic = find_characteristic(peripheral, notify_characteristic)?;
+        peripheral.subscribe(&characteristic).await?;
+        Ok(())
+    }
+
+    async fn write(
+        &self,
+        peripheral: &Self::Peripheral,
+        write_characteristic: Uuid,
+        data: &[u8],
+    ) -> anyhow::Result<()> {
+        let characteristic = find_characteristic(peripheral, write_characteristic)?;
+        peripheral
+            .write(&characteristic, data, WriteType::WithoutResponse)
+            .await?;
+        Ok(())
+    }
+
+    async fn notifications(
+        &self,
+        peripheral: &Self::Peripheral,
+        notify_characteristic: Uuid,
+    ) -> anyhow::Result<BoxStream<'static, Vec<u8>>> {
+        let notifications = peripheral.notifications().await?;
+        Ok(notifications
+            .filter(move |n| futures_util::future::ready(n.uuid == notify_characteristic))
+            .map(|n| n.value)
+            .boxed())
+    }
+}
+
+fn find_characteristic(peripheral: &Peripheral, id: Uuid) -> anyhow::Result<Characteristic> {
+    peripheral
+        .characteristics()
+        .into_iter()
+        .find(|c| c.uuid == id)
+        .ok_or_else(|| anyhow!("Characteristic {id} not found"))
+}
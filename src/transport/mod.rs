@@ -0,0 +1,54 @@
+//! A `Transport` abstraction over the BMS's BLE operations, so [`crate::jbd_transport`]
+//! doesn't care whether it's driven by `btleplug` or by the system Bluetooth daemon
+//! directly over D-Bus.
+//!
+//! `btleplug_transport` is the default backend and is what the rest of this crate is built
+//! on today. `bluez_transport`, the `bluer`/D-Bus backend, is meant to be opt-in behind a
+//! `bluez-backend` Cargo feature -- on Linux `btleplug`'s BlueZ support ultimately talks to
+//! the same daemon, but goes through a layer that has proven fragile in practice. This
+//! crate has no `Cargo.toml` checked in yet, so that feature isn't declared anywhere today;
+//! add a `bluez-backend = ["dep:bluer"]` entry (with `bluer` as an optional dependency)
+//! once one exists.
+
+#[cfg(feature = "bluez-backend")]
+pub mod bluez_transport;
+pub mod btleplug_transport;
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::stream::BoxStream;
+use uuid::Uuid;
+
+use crate::scan::ScanResult;
+
+/// BLE operations needed to talk to a BMS, independent of which library drives them.
+#[async_trait]
+pub trait Transport {
+    /// A connected, service-discovered peripheral handle.
+    type Peripheral: Send + Sync;
+
+    /// Scan for devices advertising `service` for `duration`.
+    async fn scan(&self, service: Uuid, duration: Duration) -> anyhow::Result<Vec<ScanResult>>;
+
+    /// Connect to the device found by [`Self::scan`] and discover its services.
+    async fn connect(&self, result: &ScanResult) -> anyhow::Result<Self::Peripheral>;
+
+    /// Subscribe to notifications on `notify_characteristic`.
+    async fn subscribe(&self, peripheral: &Self::Peripheral, notify_characteristic: Uuid) -> anyhow::Result<()>;
+
+    /// Write `data` to `write_characteristic`.
+    async fn write(
+        &self,
+        peripheral: &Self::Peripheral,
+        write_characteristic: Uuid,
+        data: &[u8],
+    ) -> anyhow::Result<()>;
+
+    /// A stream of raw notification payloads from `notify_characteristic`.
+    async fn notifications(
+        &self,
+        peripheral: &Self::Peripheral,
+        notify_characteristic: Uuid,
+    ) -> anyhow::Result<BoxStream<'static, Vec<u8>>>;
+}